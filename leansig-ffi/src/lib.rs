@@ -1,24 +1,33 @@
 //! C-compatible FFI wrapper around the leansig XMSS signature scheme.
 //!
 //! This crate provides a C API for the leansig library's generalized XMSS
-//! signature scheme, targeted at the devnet-1 instantiation:
-//! `SIGTopLevelTargetSumLifetime32Dim64Base8` (LOG_LIFETIME=32, DIM=64, BASE=8).
+//! signature scheme. Multiple compiled-in Poseidon instantiations (distinct
+//! LOG_LIFETIME/DIM/BASE tuples) are selectable at runtime through a
+//! `LeansigContext` handle rather than baked in at build time; see
+//! `leansig_context_new` and `SchemeId`.
 //! All types are passed as opaque pointers or SSZ-serialized byte buffers.
 //! Memory management follows the "caller frees" pattern: every `_new` or
 //! `_generate` function has a corresponding `_free` function.
 
+use std::cell::RefCell;
 use std::slice;
 
 use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 
 use leansig::serialization::Serializable;
-use leansig::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_32::hashing_optimized::SIGTopLevelTargetSumLifetime32Dim64Base8 as SigScheme;
+use leansig::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_18::hashing_optimized::SIGTopLevelTargetSumLifetime18Dim64Base8 as SigSchemeL18;
+use leansig::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_32::hashing_optimized::SIGTopLevelTargetSumLifetime32Dim64Base8 as SigSchemeL32;
 use leansig::signature::{SignatureScheme, SignatureSchemeSecretKey};
 
-// Concrete type aliases for the devnet-1 instantiation.
-type PublicKey = <SigScheme as SignatureScheme>::PublicKey;
-type SecretKey = <SigScheme as SignatureScheme>::SecretKey;
-type Signature = <SigScheme as SignatureScheme>::Signature;
+// Concrete type aliases, one family per compiled-in instantiation.
+type PublicKeyL32 = <SigSchemeL32 as SignatureScheme>::PublicKey;
+type SecretKeyL32 = <SigSchemeL32 as SignatureScheme>::SecretKey;
+type SignatureL32 = <SigSchemeL32 as SignatureScheme>::Signature;
+
+type PublicKeyL18 = <SigSchemeL18 as SignatureScheme>::PublicKey;
+type SecretKeyL18 = <SigSchemeL18 as SignatureScheme>::SecretKey;
+type SignatureL18 = <SigSchemeL18 as SignatureScheme>::Signature;
 
 /// Result codes returned by FFI functions.
 #[repr(C)]
@@ -37,21 +46,169 @@ pub enum LeansigResult {
     VerificationFailed = 5,
     /// Epoch outside prepared interval.
     EpochNotPrepared = 6,
+    /// `scheme_id` names no compiled-in instantiation, or the context's scheme
+    /// does not match the handle it was passed alongside.
+    InvalidScheme = 7,
+    /// `PublicKey::from_bytes` failed.
+    InvalidPublicKey = 8,
+    /// `SecretKey::from_bytes` failed.
+    InvalidSecretKey = 9,
+    /// `Signature::from_bytes` failed.
+    InvalidSignature = 10,
+}
+
+thread_local! {
+    /// Short UTF-8 reason for the most recent deserialization failure on this
+    /// thread, surfaced via `leansig_last_error_detail`.
+    static LAST_ERROR_DETAIL: RefCell<String> = const { RefCell::new(String::new()) };
 }
 
-/// Opaque keypair holding both public and secret keys.
+fn set_last_error_detail(detail: impl std::fmt::Display) {
+    LAST_ERROR_DETAIL.with(|cell| {
+        *cell.borrow_mut() = detail.to_string();
+    });
+}
+
+/// Get a short UTF-8 reason for the most recent deserialization failure on
+/// this thread (e.g. length mismatch vs. SSZ structure error vs. out-of-range
+/// field element), as set by `keypair_restore`, `verify`, or
+/// `verify_with_keypair`.
+///
+/// # Returns
+/// `LeansigResult::InvalidLength` if `buf_len` is smaller than the detail string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_last_error_detail(
+    out_buf: *mut u8,
+    buf_len: usize,
+    out_written: *mut usize,
+) -> LeansigResult {
+    if out_buf.is_null() || out_written.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    LAST_ERROR_DETAIL.with(|cell| {
+        let detail = cell.borrow();
+        let bytes = detail.as_bytes();
+        if buf_len < bytes.len() {
+            return LeansigResult::InvalidLength;
+        }
+        unsafe {
+            slice::from_raw_parts_mut(out_buf, bytes.len()).copy_from_slice(bytes);
+            *out_written = bytes.len();
+        }
+        LeansigResult::Ok
+    })
+}
+
+/// Identifies one of the compiled-in Poseidon XMSS instantiations.
+///
+/// Each variant corresponds to a distinct LOG_LIFETIME/DIM/BASE tuple that
+/// this library was built with monomorphized support for.
+#[repr(u32)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SchemeId {
+    /// LOG_LIFETIME=32, DIM=64, BASE=8 (the devnet-1 instantiation).
+    Lifetime32Dim64Base8 = 0,
+    /// LOG_LIFETIME=18, DIM=64, BASE=8.
+    Lifetime18Dim64Base8 = 1,
+}
+
+impl SchemeId {
+    fn from_u32(v: u32) -> Option<Self> {
+        match v {
+            0 => Some(Self::Lifetime32Dim64Base8),
+            1 => Some(Self::Lifetime18Dim64Base8),
+            _ => None,
+        }
+    }
+}
+
+/// Opaque handle selecting which compiled-in instantiation subsequent calls operate on.
+pub struct LeansigContext {
+    scheme_id: SchemeId,
+}
+
+/// Create a context for the given `scheme_id` (see `SchemeId`).
+///
+/// # Returns
+/// `LeansigResult::Ok` on success, `LeansigResult::InvalidScheme` if `scheme_id`
+/// does not name a compiled-in instantiation.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_context_new(
+    scheme_id: u32,
+    out_ctx: *mut *mut LeansigContext,
+) -> LeansigResult {
+    if out_ctx.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    let Some(scheme_id) = SchemeId::from_u32(scheme_id) else {
+        return LeansigResult::InvalidScheme;
+    };
+    let ctx = Box::new(LeansigContext { scheme_id });
+    unsafe {
+        *out_ctx = Box::into_raw(ctx);
+    }
+    LeansigResult::Ok
+}
+
+/// Free a context allocated by `leansig_context_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_context_free(ctx: *mut LeansigContext) {
+    if !ctx.is_null() {
+        unsafe {
+            drop(Box::from_raw(ctx));
+        }
+    }
+}
+
+/// Get the `SchemeId` a context was created with.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_context_scheme_id(
+    ctx: *const LeansigContext,
+    out_scheme_id: *mut u32,
+) -> LeansigResult {
+    if ctx.is_null() || out_scheme_id.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    let ctx = unsafe { &*ctx };
+    unsafe {
+        *out_scheme_id = ctx.scheme_id as u32;
+    }
+    LeansigResult::Ok
+}
+
+/// Opaque keypair holding both public and secret keys for one compiled-in scheme.
 pub struct LeansigKeypair {
-    pk: PublicKey,
-    sk: SecretKey,
+    inner: KeypairInner,
+}
+
+enum KeypairInner {
+    Lifetime32Dim64Base8 {
+        pk: PublicKeyL32,
+        sk: SecretKeyL32,
+    },
+    Lifetime18Dim64Base8 {
+        pk: PublicKeyL18,
+        sk: SecretKeyL18,
+    },
+}
+
+impl LeansigKeypair {
+    fn scheme_id(&self) -> SchemeId {
+        match &self.inner {
+            KeypairInner::Lifetime32Dim64Base8 { .. } => SchemeId::Lifetime32Dim64Base8,
+            KeypairInner::Lifetime18Dim64Base8 { .. } => SchemeId::Lifetime18Dim64Base8,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Key generation
 // ---------------------------------------------------------------------------
 
-/// Generate a new XMSS keypair.
+/// Generate a new XMSS keypair under the scheme selected by `ctx`.
 ///
 /// # Arguments
+/// * `ctx` - Context selecting which compiled-in instantiation to use.
 /// * `seed` - Random seed for the RNG (will be used to seed a ChaCha RNG).
 /// * `activation_epoch` - Starting epoch for which the key is active.
 /// * `num_active_epochs` - Number of consecutive active epochs.
@@ -63,37 +220,55 @@ pub struct LeansigKeypair {
 /// # Note
 /// Key generation is performed on a dedicated thread with a large stack
 /// (64 MB) to accommodate the deep recursion required by XMSS Merkle tree
-/// construction with LOG_LIFETIME=32.
+/// construction at large LOG_LIFETIME values.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn leansig_keypair_generate(
+    ctx: *const LeansigContext,
     seed: u64,
     activation_epoch: u64,
     num_active_epochs: u64,
     out_keypair: *mut *mut LeansigKeypair,
 ) -> LeansigResult {
-    if out_keypair.is_null() {
+    if ctx.is_null() || out_keypair.is_null() {
         return LeansigResult::NullPointer;
     }
+    let ctx = unsafe { &*ctx };
 
     // Spawn key_gen on a thread with 64 MB stack to avoid stack overflow
-    // from deep Merkle tree recursion in the LOG_LIFETIME=32 instantiation.
+    // from deep Merkle tree recursion in the larger LOG_LIFETIME instantiations.
     const STACK_SIZE: usize = 64 * 1024 * 1024; // 64 MB
+    let scheme_id = ctx.scheme_id;
     let handle = std::thread::Builder::new()
         .stack_size(STACK_SIZE)
         .spawn(move || {
             let mut rng = rand::rngs::SmallRng::seed_from_u64(seed);
-            SigScheme::key_gen(
-                &mut rng,
-                activation_epoch as usize,
-                num_active_epochs as usize,
-            )
+            match scheme_id {
+                SchemeId::Lifetime32Dim64Base8 => {
+                    let (pk, sk) = SigSchemeL32::key_gen(
+                        &mut rng,
+                        activation_epoch as usize,
+                        num_active_epochs as usize,
+                    );
+                    KeypairInner::Lifetime32Dim64Base8 { pk, sk }
+                }
+                SchemeId::Lifetime18Dim64Base8 => {
+                    let (pk, sk) = SigSchemeL18::key_gen(
+                        &mut rng,
+                        activation_epoch as usize,
+                        num_active_epochs as usize,
+                    );
+                    KeypairInner::Lifetime18Dim64Base8 { pk, sk }
+                }
+            }
         });
 
     match handle {
         Ok(join_handle) => match join_handle.join() {
-            Ok((pk, sk)) => {
-                let keypair = Box::new(LeansigKeypair { pk, sk });
-                *out_keypair = Box::into_raw(keypair);
+            Ok(inner) => {
+                let keypair = Box::new(LeansigKeypair { inner });
+                unsafe {
+                    *out_keypair = Box::into_raw(keypair);
+                }
                 LeansigResult::Ok
             }
             Err(_) => LeansigResult::SigningFailed, // thread panicked
@@ -102,9 +277,11 @@ pub unsafe extern "C" fn leansig_keypair_generate(
     }
 }
 
-/// Restore a keypair from serialized public and secret key bytes.
+/// Restore a keypair from serialized public and secret key bytes under the
+/// scheme selected by `ctx`.
 ///
 /// # Arguments
+/// * `ctx` - Context selecting which compiled-in instantiation to parse the bytes as.
 /// * `pk_bytes` - Pointer to the serialized public key bytes.
 /// * `pk_len` - Length of the public key bytes.
 /// * `sk_bytes` - Pointer to the serialized secret key bytes.
@@ -112,34 +289,66 @@ pub unsafe extern "C" fn leansig_keypair_generate(
 /// * `out_keypair` - Pointer to receive the opaque keypair handle.
 ///
 /// # Returns
-/// `LeansigResult::Ok` on success, or `DeserializationFailed` if bytes are invalid.
+/// `LeansigResult::Ok` on success, `InvalidPublicKey`/`InvalidSecretKey` if the
+/// respective bytes are malformed; `leansig_last_error_detail` carries the reason.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn leansig_keypair_restore(
+    ctx: *const LeansigContext,
     pk_bytes: *const u8,
     pk_len: usize,
     sk_bytes: *const u8,
     sk_len: usize,
     out_keypair: *mut *mut LeansigKeypair,
 ) -> LeansigResult {
-    if pk_bytes.is_null() || sk_bytes.is_null() || out_keypair.is_null() {
+    if ctx.is_null() || pk_bytes.is_null() || sk_bytes.is_null() || out_keypair.is_null() {
         return LeansigResult::NullPointer;
     }
+    let ctx = unsafe { &*ctx };
 
-    let pk_slice = slice::from_raw_parts(pk_bytes, pk_len);
-    let sk_slice = slice::from_raw_parts(sk_bytes, sk_len);
-
-    let pk = match PublicKey::from_bytes(pk_slice) {
-        Ok(k) => k,
-        Err(_) => return LeansigResult::DeserializationFailed,
-    };
+    let pk_slice = unsafe { slice::from_raw_parts(pk_bytes, pk_len) };
+    let sk_slice = unsafe { slice::from_raw_parts(sk_bytes, sk_len) };
 
-    let sk = match SecretKey::from_bytes(sk_slice) {
-        Ok(k) => k,
-        Err(_) => return LeansigResult::DeserializationFailed,
+    let inner = match ctx.scheme_id {
+        SchemeId::Lifetime32Dim64Base8 => {
+            let pk = match PublicKeyL32::from_bytes(pk_slice) {
+                Ok(k) => k,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidPublicKey;
+                }
+            };
+            let sk = match SecretKeyL32::from_bytes(sk_slice) {
+                Ok(k) => k,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidSecretKey;
+                }
+            };
+            KeypairInner::Lifetime32Dim64Base8 { pk, sk }
+        }
+        SchemeId::Lifetime18Dim64Base8 => {
+            let pk = match PublicKeyL18::from_bytes(pk_slice) {
+                Ok(k) => k,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidPublicKey;
+                }
+            };
+            let sk = match SecretKeyL18::from_bytes(sk_slice) {
+                Ok(k) => k,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidSecretKey;
+                }
+            };
+            KeypairInner::Lifetime18Dim64Base8 { pk, sk }
+        }
     };
 
-    let keypair = Box::new(LeansigKeypair { pk, sk });
-    *out_keypair = Box::into_raw(keypair);
+    let keypair = Box::new(LeansigKeypair { inner });
+    unsafe {
+        *out_keypair = Box::into_raw(keypair);
+    }
     LeansigResult::Ok
 }
 
@@ -157,64 +366,241 @@ pub unsafe extern "C" fn leansig_keypair_free(keypair: *mut LeansigKeypair) {
 // Public key serialization
 // ---------------------------------------------------------------------------
 
+/// Get the exact length in bytes of this keypair's SSZ-serialized public key.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_pubkey_size(
+    ctx: *const LeansigContext,
+    keypair: *const LeansigKeypair,
+    out_size: *mut usize,
+) -> LeansigResult {
+    if ctx.is_null() || keypair.is_null() || out_size.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    let ctx = unsafe { &*ctx };
+    let keypair = unsafe { &*keypair };
+    if ctx.scheme_id != keypair.scheme_id() {
+        return LeansigResult::InvalidScheme;
+    }
+
+    let size = match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { .. } => SigSchemeL32::PUBLIC_KEY_LENGTH,
+        KeypairInner::Lifetime18Dim64Base8 { .. } => SigSchemeL18::PUBLIC_KEY_LENGTH,
+    };
+    unsafe {
+        *out_size = size;
+    }
+    LeansigResult::Ok
+}
+
+/// Get the exact length in bytes of this keypair's SSZ-serialized secret key.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_seckey_size(
+    ctx: *const LeansigContext,
+    keypair: *const LeansigKeypair,
+    out_size: *mut usize,
+) -> LeansigResult {
+    if ctx.is_null() || keypair.is_null() || out_size.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    let ctx = unsafe { &*ctx };
+    let keypair = unsafe { &*keypair };
+    if ctx.scheme_id != keypair.scheme_id() {
+        return LeansigResult::InvalidScheme;
+    }
+
+    let size = match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { .. } => SigSchemeL32::SECRET_KEY_LENGTH,
+        KeypairInner::Lifetime18Dim64Base8 { .. } => SigSchemeL18::SECRET_KEY_LENGTH,
+    };
+    unsafe {
+        *out_size = size;
+    }
+    LeansigResult::Ok
+}
+
+/// Get the exact length in bytes of an SSZ-serialized signature under `ctx`'s scheme.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_signature_size(
+    ctx: *const LeansigContext,
+    out_size: *mut usize,
+) -> LeansigResult {
+    if ctx.is_null() || out_size.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    let ctx = unsafe { &*ctx };
+
+    let size = match ctx.scheme_id {
+        SchemeId::Lifetime32Dim64Base8 => SigSchemeL32::SIGNATURE_LENGTH,
+        SchemeId::Lifetime18Dim64Base8 => SigSchemeL18::SIGNATURE_LENGTH,
+    };
+    unsafe {
+        *out_size = size;
+    }
+    LeansigResult::Ok
+}
+
+/// Serialize a keypair's public key into a caller-owned buffer.
+///
+/// # Arguments
+/// * `ctx` - Context; must select the same scheme the keypair was created with.
+/// * `keypair` - Opaque keypair handle.
+/// * `out_buf` - Caller-owned buffer of at least `leansig_pubkey_size(ctx, keypair)` bytes.
+/// * `buf_len` - Length of `out_buf`.
+/// * `out_written` - Pointer to receive the number of bytes written.
+///
+/// # Returns
+/// `LeansigResult::InvalidLength` if `buf_len` is smaller than the serialized size.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_pubkey_serialize_into(
+    ctx: *const LeansigContext,
+    keypair: *const LeansigKeypair,
+    out_buf: *mut u8,
+    buf_len: usize,
+    out_written: *mut usize,
+) -> LeansigResult {
+    if ctx.is_null() || keypair.is_null() || out_buf.is_null() || out_written.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    let ctx = unsafe { &*ctx };
+    let keypair = unsafe { &*keypair };
+    if ctx.scheme_id != keypair.scheme_id() {
+        return LeansigResult::InvalidScheme;
+    }
+
+    let bytes = match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { pk, .. } => pk.to_bytes(),
+        KeypairInner::Lifetime18Dim64Base8 { pk, .. } => pk.to_bytes(),
+    };
+    if buf_len < bytes.len() {
+        return LeansigResult::InvalidLength;
+    }
+
+    unsafe {
+        slice::from_raw_parts_mut(out_buf, bytes.len()).copy_from_slice(&bytes);
+        *out_written = bytes.len();
+    }
+    LeansigResult::Ok
+}
+
+/// Serialize a keypair's secret key into a caller-owned buffer.
+///
+/// See `leansig_pubkey_serialize_into` for the calling convention.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_seckey_serialize_into(
+    ctx: *const LeansigContext,
+    keypair: *const LeansigKeypair,
+    out_buf: *mut u8,
+    buf_len: usize,
+    out_written: *mut usize,
+) -> LeansigResult {
+    if ctx.is_null() || keypair.is_null() || out_buf.is_null() || out_written.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    let ctx = unsafe { &*ctx };
+    let keypair = unsafe { &*keypair };
+    if ctx.scheme_id != keypair.scheme_id() {
+        return LeansigResult::InvalidScheme;
+    }
+
+    let bytes = match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { sk, .. } => sk.to_bytes(),
+        KeypairInner::Lifetime18Dim64Base8 { sk, .. } => sk.to_bytes(),
+    };
+    if buf_len < bytes.len() {
+        return LeansigResult::InvalidLength;
+    }
+
+    unsafe {
+        slice::from_raw_parts_mut(out_buf, bytes.len()).copy_from_slice(&bytes);
+        *out_written = bytes.len();
+    }
+    LeansigResult::Ok
+}
+
 /// Get the SSZ-serialized public key from a keypair.
 ///
-/// The caller must free the returned buffer with `leansig_bytes_free`.
+/// Thin allocating wrapper around `leansig_pubkey_serialize_into` for callers
+/// that don't want to manage their own buffer. The caller must free the
+/// returned buffer with `leansig_bytes_free`.
 ///
 /// # Arguments
+/// * `ctx` - Context; must select the same scheme the keypair was created with.
 /// * `keypair` - Opaque keypair handle.
 /// * `out_data` - Pointer to receive the byte buffer.
 /// * `out_len` - Pointer to receive the buffer length.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn leansig_pubkey_serialize(
+    ctx: *const LeansigContext,
     keypair: *const LeansigKeypair,
     out_data: *mut *mut u8,
     out_len: *mut usize,
 ) -> LeansigResult {
-    if keypair.is_null() || out_data.is_null() || out_len.is_null() {
+    if ctx.is_null() || keypair.is_null() || out_data.is_null() || out_len.is_null() {
         return LeansigResult::NullPointer;
     }
 
-    let keypair = unsafe { &*keypair };
-    let bytes = keypair.pk.to_bytes();
+    let mut size = 0usize;
+    let result = unsafe { leansig_pubkey_size(ctx, keypair, &mut size) };
+    if !matches!(result, LeansigResult::Ok) {
+        return result;
+    }
 
-    let len = bytes.len();
-    let ptr = bytes.leak().as_mut_ptr();
+    let mut buf = vec![0u8; size];
+    let mut written = 0usize;
+    let result =
+        unsafe { leansig_pubkey_serialize_into(ctx, keypair, buf.as_mut_ptr(), size, &mut written) };
+    if !matches!(result, LeansigResult::Ok) {
+        return result;
+    }
 
+    let ptr = buf.leak().as_mut_ptr();
     unsafe {
         *out_data = ptr;
-        *out_len = len;
+        *out_len = written;
     }
     LeansigResult::Ok
 }
 
 /// Get the SSZ-serialized secret key from a keypair.
 ///
-/// The caller must free the returned buffer with `leansig_bytes_free`.
+/// Thin allocating wrapper around `leansig_seckey_serialize_into` for callers
+/// that don't want to manage their own buffer. The caller must free the
+/// returned buffer with `leansig_bytes_free`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn leansig_seckey_serialize(
+    ctx: *const LeansigContext,
     keypair: *const LeansigKeypair,
     out_data: *mut *mut u8,
     out_len: *mut usize,
 ) -> LeansigResult {
-    if keypair.is_null() || out_data.is_null() || out_len.is_null() {
+    if ctx.is_null() || keypair.is_null() || out_data.is_null() || out_len.is_null() {
         return LeansigResult::NullPointer;
     }
 
-    let keypair = unsafe { &*keypair };
-    let bytes = keypair.sk.to_bytes();
+    let mut size = 0usize;
+    let result = unsafe { leansig_seckey_size(ctx, keypair, &mut size) };
+    if !matches!(result, LeansigResult::Ok) {
+        return result;
+    }
 
-    let len = bytes.len();
-    let ptr = bytes.leak().as_mut_ptr();
+    let mut buf = vec![0u8; size];
+    let mut written = 0usize;
+    let result =
+        unsafe { leansig_seckey_serialize_into(ctx, keypair, buf.as_mut_ptr(), size, &mut written) };
+    if !matches!(result, LeansigResult::Ok) {
+        return result;
+    }
 
+    let ptr = buf.leak().as_mut_ptr();
     unsafe {
         *out_data = ptr;
-        *out_len = len;
+        *out_len = written;
     }
     LeansigResult::Ok
 }
 
-/// Free a byte buffer returned by any `leansig_*_serialize` function.
+/// Free a byte buffer returned by any allocating `leansig_*_serialize` function,
+/// `leansig_sign`, or `leansig_sign_deterministic`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn leansig_bytes_free(data: *mut u8, len: usize) {
     if !data.is_null() && len > 0 {
@@ -235,7 +621,10 @@ pub unsafe extern "C" fn leansig_sk_activation_start(keypair: *const LeansigKeyp
         return 0;
     }
     let keypair = unsafe { &*keypair };
-    keypair.sk.get_activation_interval().start
+    match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { sk, .. } => sk.get_activation_interval().start,
+        KeypairInner::Lifetime18Dim64Base8 { sk, .. } => sk.get_activation_interval().start,
+    }
 }
 
 /// Get the end (exclusive) of the activation interval for this secret key.
@@ -245,7 +634,10 @@ pub unsafe extern "C" fn leansig_sk_activation_end(keypair: *const LeansigKeypai
         return 0;
     }
     let keypair = unsafe { &*keypair };
-    keypair.sk.get_activation_interval().end
+    match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { sk, .. } => sk.get_activation_interval().end,
+        KeypairInner::Lifetime18Dim64Base8 { sk, .. } => sk.get_activation_interval().end,
+    }
 }
 
 /// Get the start of the currently prepared interval.
@@ -255,7 +647,10 @@ pub unsafe extern "C" fn leansig_sk_prepared_start(keypair: *const LeansigKeypai
         return 0;
     }
     let keypair = unsafe { &*keypair };
-    keypair.sk.get_prepared_interval().start
+    match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { sk, .. } => sk.get_prepared_interval().start,
+        KeypairInner::Lifetime18Dim64Base8 { sk, .. } => sk.get_prepared_interval().start,
+    }
 }
 
 /// Get the end (exclusive) of the currently prepared interval.
@@ -265,7 +660,10 @@ pub unsafe extern "C" fn leansig_sk_prepared_end(keypair: *const LeansigKeypair)
         return 0;
     }
     let keypair = unsafe { &*keypair };
-    keypair.sk.get_prepared_interval().end
+    match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { sk, .. } => sk.get_prepared_interval().end,
+        KeypairInner::Lifetime18Dim64Base8 { sk, .. } => sk.get_prepared_interval().end,
+    }
 }
 
 /// Advance the secret key's prepared interval to the next window.
@@ -277,7 +675,10 @@ pub unsafe extern "C" fn leansig_sk_advance_preparation(
         return LeansigResult::NullPointer;
     }
     let keypair = unsafe { &mut *keypair };
-    keypair.sk.advance_preparation();
+    match &mut keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { sk, .. } => sk.advance_preparation(),
+        KeypairInner::Lifetime18Dim64Base8 { sk, .. } => sk.advance_preparation(),
+    }
     LeansigResult::Ok
 }
 
@@ -290,6 +691,7 @@ pub unsafe extern "C" fn leansig_sk_advance_preparation(
 /// The caller must free the returned signature buffer with `leansig_bytes_free`.
 ///
 /// # Arguments
+/// * `ctx` - Context; must select the same scheme the keypair was created with.
 /// * `keypair` - Opaque keypair handle (secret key is used).
 /// * `epoch` - The epoch to sign at (must be in the prepared interval).
 /// * `message` - Pointer to 32-byte message.
@@ -297,37 +699,137 @@ pub unsafe extern "C" fn leansig_sk_advance_preparation(
 /// * `out_sig_len` - Pointer to receive the signature length.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn leansig_sign(
+    ctx: *const LeansigContext,
     keypair: *const LeansigKeypair,
     epoch: u32,
     message: *const u8,
     out_sig_data: *mut *mut u8,
     out_sig_len: *mut usize,
 ) -> LeansigResult {
-    if keypair.is_null() || message.is_null() || out_sig_data.is_null() || out_sig_len.is_null() {
+    if ctx.is_null()
+        || keypair.is_null()
+        || message.is_null()
+        || out_sig_data.is_null()
+        || out_sig_len.is_null()
+    {
         return LeansigResult::NullPointer;
     }
-
+    let ctx = unsafe { &*ctx };
     let keypair = unsafe { &*keypair };
+    if ctx.scheme_id != keypair.scheme_id() {
+        return LeansigResult::InvalidScheme;
+    }
     let msg: &[u8; 32] = unsafe { &*(message as *const [u8; 32]) };
 
-    // Check epoch is in prepared interval
-    if !keypair.sk.get_prepared_interval().contains(&(epoch as u64)) {
-        return LeansigResult::EpochNotPrepared;
+    let bytes = match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { sk, .. } => {
+            if !sk.get_prepared_interval().contains(&(epoch as u64)) {
+                return LeansigResult::EpochNotPrepared;
+            }
+            match SigSchemeL32::sign(sk, epoch, msg) {
+                Ok(sig) => sig.to_bytes(),
+                Err(_) => return LeansigResult::SigningFailed,
+            }
+        }
+        KeypairInner::Lifetime18Dim64Base8 { sk, .. } => {
+            if !sk.get_prepared_interval().contains(&(epoch as u64)) {
+                return LeansigResult::EpochNotPrepared;
+            }
+            match SigSchemeL18::sign(sk, epoch, msg) {
+                Ok(sig) => sig.to_bytes(),
+                Err(_) => return LeansigResult::SigningFailed,
+            }
+        }
+    };
+
+    let len = bytes.len();
+    let ptr = bytes.leak().as_mut_ptr();
+    unsafe {
+        *out_sig_data = ptr;
+        *out_sig_len = len;
     }
+    LeansigResult::Ok
+}
 
-    match SigScheme::sign(&keypair.sk, epoch, msg) {
-        Ok(sig) => {
-            let bytes = sig.to_bytes();
-            let len = bytes.len();
-            let ptr = bytes.leak().as_mut_ptr();
-            unsafe {
-                *out_sig_data = ptr;
-                *out_sig_len = len;
+/// Sign a 32-byte message at a given epoch with a caller-supplied randomizer seed.
+///
+/// The target-sum encoding retries with a fresh internal randomizer until the
+/// encoded chunks hit the target sum; seeding that randomizer from `rho_seed`
+/// makes the resulting signature a pure function of `(sk, epoch, message,
+/// rho_seed)`, reproducible across calls and platforms. The number of
+/// encoding attempts actually consumed is written to `out_attempts`.
+///
+/// The caller must free the returned signature buffer with `leansig_bytes_free`.
+///
+/// # Arguments
+/// * `ctx` - Context; must select the same scheme the keypair was created with.
+/// * `keypair` - Opaque keypair handle (secret key is used).
+/// * `epoch` - The epoch to sign at (must be in the prepared interval).
+/// * `message` - Pointer to 32-byte message.
+/// * `rho_seed` - Seed for the deterministic encoding randomizer.
+/// * `out_sig_data` - Pointer to receive the SSZ-serialized signature bytes.
+/// * `out_sig_len` - Pointer to receive the signature length.
+/// * `out_attempts` - Pointer to receive the number of encoding attempts consumed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_sign_deterministic(
+    ctx: *const LeansigContext,
+    keypair: *const LeansigKeypair,
+    epoch: u32,
+    message: *const u8,
+    rho_seed: u64,
+    out_sig_data: *mut *mut u8,
+    out_sig_len: *mut usize,
+    out_attempts: *mut u32,
+) -> LeansigResult {
+    if ctx.is_null()
+        || keypair.is_null()
+        || message.is_null()
+        || out_sig_data.is_null()
+        || out_sig_len.is_null()
+        || out_attempts.is_null()
+    {
+        return LeansigResult::NullPointer;
+    }
+    let ctx = unsafe { &*ctx };
+    let keypair = unsafe { &*keypair };
+    if ctx.scheme_id != keypair.scheme_id() {
+        return LeansigResult::InvalidScheme;
+    }
+    let msg: &[u8; 32] = unsafe { &*(message as *const [u8; 32]) };
+
+    // ChaCha20Rng (unlike SmallRng) has a fixed, versioned stream that does not
+    // vary with target word size, so the same rho_seed reproduces the same
+    // signature on every platform.
+    let mut rng = ChaCha20Rng::seed_from_u64(rho_seed);
+    let (bytes, attempts) = match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { sk, .. } => {
+            if !sk.get_prepared_interval().contains(&(epoch as u64)) {
+                return LeansigResult::EpochNotPrepared;
+            }
+            match SigSchemeL32::sign_deterministic(sk, epoch, msg, &mut rng) {
+                Ok((sig, attempts)) => (sig.to_bytes(), attempts),
+                Err(_) => return LeansigResult::SigningFailed,
+            }
+        }
+        KeypairInner::Lifetime18Dim64Base8 { sk, .. } => {
+            if !sk.get_prepared_interval().contains(&(epoch as u64)) {
+                return LeansigResult::EpochNotPrepared;
+            }
+            match SigSchemeL18::sign_deterministic(sk, epoch, msg, &mut rng) {
+                Ok((sig, attempts)) => (sig.to_bytes(), attempts),
+                Err(_) => return LeansigResult::SigningFailed,
             }
-            LeansigResult::Ok
         }
-        Err(_) => LeansigResult::SigningFailed,
+    };
+
+    let len = bytes.len();
+    let ptr = bytes.leak().as_mut_ptr();
+    unsafe {
+        *out_sig_data = ptr;
+        *out_sig_len = len;
+        *out_attempts = attempts;
     }
+    LeansigResult::Ok
 }
 
 // ---------------------------------------------------------------------------
@@ -337,6 +839,7 @@ pub unsafe extern "C" fn leansig_sign(
 /// Verify a signature against a public key, epoch, and message.
 ///
 /// # Arguments
+/// * `ctx` - Context selecting which compiled-in instantiation to parse the bytes as.
 /// * `pk_data` - SSZ-serialized public key bytes.
 /// * `pk_len` - Length of public key bytes.
 /// * `epoch` - The epoch the signature was created at.
@@ -345,9 +848,12 @@ pub unsafe extern "C" fn leansig_sign(
 /// * `sig_len` - Length of signature bytes.
 ///
 /// # Returns
-/// `LeansigResult::Ok` if verification succeeds, `LeansigResult::VerificationFailed` otherwise.
+/// `LeansigResult::Ok` if verification succeeds, `LeansigResult::VerificationFailed` if
+/// it fails cleanly, or `InvalidPublicKey`/`InvalidSignature` if the respective bytes
+/// are malformed (`leansig_last_error_detail` carries the reason).
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn leansig_verify(
+    ctx: *const LeansigContext,
     pk_data: *const u8,
     pk_len: usize,
     epoch: u32,
@@ -355,25 +861,53 @@ pub unsafe extern "C" fn leansig_verify(
     sig_data: *const u8,
     sig_len: usize,
 ) -> LeansigResult {
-    if pk_data.is_null() || message.is_null() || sig_data.is_null() {
+    if ctx.is_null() || pk_data.is_null() || message.is_null() || sig_data.is_null() {
         return LeansigResult::NullPointer;
     }
+    let ctx = unsafe { &*ctx };
 
     let pk_bytes = unsafe { slice::from_raw_parts(pk_data, pk_len) };
     let sig_bytes = unsafe { slice::from_raw_parts(sig_data, sig_len) };
     let msg: &[u8; 32] = unsafe { &*(message as *const [u8; 32]) };
 
-    let pk = match PublicKey::from_bytes(pk_bytes) {
-        Ok(pk) => pk,
-        Err(_) => return LeansigResult::DeserializationFailed,
-    };
-
-    let sig = match Signature::from_bytes(sig_bytes) {
-        Ok(sig) => sig,
-        Err(_) => return LeansigResult::DeserializationFailed,
+    let valid = match ctx.scheme_id {
+        SchemeId::Lifetime32Dim64Base8 => {
+            let pk = match PublicKeyL32::from_bytes(pk_bytes) {
+                Ok(pk) => pk,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidPublicKey;
+                }
+            };
+            let sig = match SignatureL32::from_bytes(sig_bytes) {
+                Ok(sig) => sig,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidSignature;
+                }
+            };
+            SigSchemeL32::verify(&pk, epoch, msg, &sig)
+        }
+        SchemeId::Lifetime18Dim64Base8 => {
+            let pk = match PublicKeyL18::from_bytes(pk_bytes) {
+                Ok(pk) => pk,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidPublicKey;
+                }
+            };
+            let sig = match SignatureL18::from_bytes(sig_bytes) {
+                Ok(sig) => sig,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidSignature;
+                }
+            };
+            SigSchemeL18::verify(&pk, epoch, msg, &sig)
+        }
     };
 
-    if SigScheme::verify(&pk, epoch, msg, &sig) {
+    if valid {
         LeansigResult::Ok
     } else {
         LeansigResult::VerificationFailed
@@ -389,28 +923,702 @@ pub unsafe extern "C" fn leansig_verify(
 /// Convenience wrapper that avoids serialization/deserialization of the public key.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn leansig_verify_with_keypair(
+    ctx: *const LeansigContext,
     keypair: *const LeansigKeypair,
     epoch: u32,
     message: *const u8,
     sig_data: *const u8,
     sig_len: usize,
 ) -> LeansigResult {
-    if keypair.is_null() || message.is_null() || sig_data.is_null() {
+    if ctx.is_null() || keypair.is_null() || message.is_null() || sig_data.is_null() {
         return LeansigResult::NullPointer;
     }
-
+    let ctx = unsafe { &*ctx };
     let keypair = unsafe { &*keypair };
+    if ctx.scheme_id != keypair.scheme_id() {
+        return LeansigResult::InvalidScheme;
+    }
+
     let sig_bytes = unsafe { slice::from_raw_parts(sig_data, sig_len) };
     let msg: &[u8; 32] = unsafe { &*(message as *const [u8; 32]) };
 
-    let sig = match Signature::from_bytes(sig_bytes) {
-        Ok(sig) => sig,
-        Err(_) => return LeansigResult::DeserializationFailed,
+    let valid = match &keypair.inner {
+        KeypairInner::Lifetime32Dim64Base8 { pk, .. } => {
+            let sig = match SignatureL32::from_bytes(sig_bytes) {
+                Ok(sig) => sig,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidSignature;
+                }
+            };
+            SigSchemeL32::verify(pk, epoch, msg, &sig)
+        }
+        KeypairInner::Lifetime18Dim64Base8 { pk, .. } => {
+            let sig = match SignatureL18::from_bytes(sig_bytes) {
+                Ok(sig) => sig,
+                Err(e) => {
+                    set_last_error_detail(e);
+                    return LeansigResult::InvalidSignature;
+                }
+            };
+            SigSchemeL18::verify(pk, epoch, msg, &sig)
+        }
     };
 
-    if SigScheme::verify(&keypair.pk, epoch, msg, &sig) {
+    if valid {
         LeansigResult::Ok
     } else {
         LeansigResult::VerificationFailed
     }
 }
+
+// ---------------------------------------------------------------------------
+// Batch verification
+// ---------------------------------------------------------------------------
+
+/// One deserialized verification job, owned so it can be moved onto a worker thread.
+enum BatchJob {
+    Lifetime32Dim64Base8 {
+        pk: PublicKeyL32,
+        epoch: u32,
+        msg: [u8; 32],
+        sig: SignatureL32,
+    },
+    Lifetime18Dim64Base8 {
+        pk: PublicKeyL18,
+        epoch: u32,
+        msg: [u8; 32],
+        sig: SignatureL18,
+    },
+}
+
+impl BatchJob {
+    fn verify(&self) -> bool {
+        match self {
+            BatchJob::Lifetime32Dim64Base8 {
+                pk,
+                epoch,
+                msg,
+                sig,
+            } => SigSchemeL32::verify(pk, *epoch, msg, sig),
+            BatchJob::Lifetime18Dim64Base8 {
+                pk,
+                epoch,
+                msg,
+                sig,
+            } => SigSchemeL18::verify(pk, *epoch, msg, sig),
+        }
+    }
+}
+
+/// Verify `n` independent signatures, all under the scheme selected by `ctx`,
+/// in parallel across a worker thread pool.
+///
+/// All inputs are deserialized up front; the whole call fails with
+/// `DeserializationFailed` if any public key or signature buffer is malformed.
+/// Otherwise each job's verification runs on a worker thread and its outcome
+/// is written to `out_results[i]` as 1 (valid) or 0 (invalid), so a caller
+/// verifying a full block of signatures pays a single FFI crossing instead of `n`.
+///
+/// # Arguments
+/// * `ctx` - Context selecting which compiled-in instantiation to parse the bytes as.
+/// * `n` - Number of verification jobs.
+/// * `pk_ptrs` / `pk_lens` - Arrays of length `n` with each SSZ-serialized public key.
+/// * `epochs` - Array of length `n` with the epoch for each job.
+/// * `msgs` - Array of length `n` of pointers, each to a 32-byte message.
+/// * `sig_ptrs` / `sig_lens` - Arrays of length `n` with each SSZ-serialized signature.
+/// * `out_results` - Caller-owned buffer of length `n`; receives 1/0 per job.
+///
+/// # Returns
+/// `LeansigResult::Ok` if the batch ran, regardless of individual outcomes.
+/// `LeansigResult::DeserializationFailed` if any input buffer is malformed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_verify_batch(
+    ctx: *const LeansigContext,
+    n: usize,
+    pk_ptrs: *const *const u8,
+    pk_lens: *const usize,
+    epochs: *const u32,
+    msgs: *const *const u8,
+    sig_ptrs: *const *const u8,
+    sig_lens: *const usize,
+    out_results: *mut u8,
+) -> LeansigResult {
+    if ctx.is_null() {
+        return LeansigResult::NullPointer;
+    }
+    let ctx = unsafe { &*ctx };
+
+    if n == 0 {
+        return LeansigResult::Ok;
+    }
+    if pk_ptrs.is_null()
+        || pk_lens.is_null()
+        || epochs.is_null()
+        || msgs.is_null()
+        || sig_ptrs.is_null()
+        || sig_lens.is_null()
+        || out_results.is_null()
+    {
+        return LeansigResult::NullPointer;
+    }
+
+    let pk_ptrs = unsafe { slice::from_raw_parts(pk_ptrs, n) };
+    let pk_lens = unsafe { slice::from_raw_parts(pk_lens, n) };
+    let epochs = unsafe { slice::from_raw_parts(epochs, n) };
+    let msgs = unsafe { slice::from_raw_parts(msgs, n) };
+    let sig_ptrs = unsafe { slice::from_raw_parts(sig_ptrs, n) };
+    let sig_lens = unsafe { slice::from_raw_parts(sig_lens, n) };
+
+    let mut jobs = Vec::with_capacity(n);
+    for i in 0..n {
+        if pk_ptrs[i].is_null() || msgs[i].is_null() || sig_ptrs[i].is_null() {
+            return LeansigResult::NullPointer;
+        }
+
+        let pk_bytes = unsafe { slice::from_raw_parts(pk_ptrs[i], pk_lens[i]) };
+        let sig_bytes = unsafe { slice::from_raw_parts(sig_ptrs[i], sig_lens[i]) };
+        let msg: [u8; 32] = unsafe { *(msgs[i] as *const [u8; 32]) };
+        let epoch = epochs[i];
+
+        let job = match ctx.scheme_id {
+            SchemeId::Lifetime32Dim64Base8 => {
+                let pk = match PublicKeyL32::from_bytes(pk_bytes) {
+                    Ok(pk) => pk,
+                    Err(_) => return LeansigResult::DeserializationFailed,
+                };
+                let sig = match SignatureL32::from_bytes(sig_bytes) {
+                    Ok(sig) => sig,
+                    Err(_) => return LeansigResult::DeserializationFailed,
+                };
+                BatchJob::Lifetime32Dim64Base8 {
+                    pk,
+                    epoch,
+                    msg,
+                    sig,
+                }
+            }
+            SchemeId::Lifetime18Dim64Base8 => {
+                let pk = match PublicKeyL18::from_bytes(pk_bytes) {
+                    Ok(pk) => pk,
+                    Err(_) => return LeansigResult::DeserializationFailed,
+                };
+                let sig = match SignatureL18::from_bytes(sig_bytes) {
+                    Ok(sig) => sig,
+                    Err(_) => return LeansigResult::DeserializationFailed,
+                };
+                BatchJob::Lifetime18Dim64Base8 {
+                    pk,
+                    epoch,
+                    msg,
+                    sig,
+                }
+            }
+        };
+        jobs.push(job);
+    }
+
+    // Carries the output buffer across worker threads; each job writes to a
+    // disjoint index, so concurrent access is safe despite the raw pointer.
+    struct OutResults(*mut u8);
+    unsafe impl Send for OutResults {}
+    unsafe impl Sync for OutResults {}
+    let out = OutResults(out_results);
+
+    let num_workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(n);
+    let chunk_size = n.div_ceil(num_workers);
+
+    std::thread::scope(|scope| {
+        for (chunk_idx, chunk) in jobs.chunks(chunk_size).enumerate() {
+            let out = &out;
+            let base = chunk_idx * chunk_size;
+            scope.spawn(move || {
+                for (offset, job) in chunk.iter().enumerate() {
+                    let valid = job.verify();
+                    unsafe {
+                        *out.0.add(base + offset) = valid as u8;
+                    }
+                }
+            });
+        }
+    });
+
+    LeansigResult::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENTINEL_WRITTEN: usize = 0xdead_beef;
+
+    unsafe fn new_context(scheme_id: SchemeId) -> *mut LeansigContext {
+        let mut ctx = std::ptr::null_mut();
+        let result = unsafe { leansig_context_new(scheme_id as u32, &mut ctx) };
+        assert!(matches!(result, LeansigResult::Ok));
+        ctx
+    }
+
+    unsafe fn new_keypair(ctx: *const LeansigContext, seed: u64) -> *mut LeansigKeypair {
+        let mut keypair = std::ptr::null_mut();
+        let result = unsafe { leansig_keypair_generate(ctx, seed, 0, 8, &mut keypair) };
+        assert!(matches!(result, LeansigResult::Ok));
+        keypair
+    }
+
+    #[test]
+    fn sign_deterministic_is_reproducible_and_verifies() {
+        unsafe {
+            let ctx = new_context(SchemeId::Lifetime18Dim64Base8);
+            let keypair = new_keypair(ctx, 6);
+            let msg = [7u8; 32];
+            let rho_seed = 42u64;
+
+            let mut sig_data_a = std::ptr::null_mut();
+            let mut sig_len_a = 0usize;
+            let mut attempts_a = 0u32;
+            let result = leansig_sign_deterministic(
+                ctx,
+                keypair,
+                0,
+                msg.as_ptr(),
+                rho_seed,
+                &mut sig_data_a,
+                &mut sig_len_a,
+                &mut attempts_a,
+            );
+            assert!(matches!(result, LeansigResult::Ok));
+            assert!(attempts_a >= 1);
+
+            let mut sig_data_b = std::ptr::null_mut();
+            let mut sig_len_b = 0usize;
+            let mut attempts_b = 0u32;
+            let result = leansig_sign_deterministic(
+                ctx,
+                keypair,
+                0,
+                msg.as_ptr(),
+                rho_seed,
+                &mut sig_data_b,
+                &mut sig_len_b,
+                &mut attempts_b,
+            );
+            assert!(matches!(result, LeansigResult::Ok));
+
+            // Same (keypair, epoch, message, rho_seed) must yield a byte-identical
+            // signature and the same attempt count, every time.
+            assert_eq!(attempts_a, attempts_b);
+            assert_eq!(
+                slice::from_raw_parts(sig_data_a, sig_len_a),
+                slice::from_raw_parts(sig_data_b, sig_len_b),
+            );
+
+            let result =
+                leansig_verify_with_keypair(ctx, keypair, 0, msg.as_ptr(), sig_data_a, sig_len_a);
+            assert!(matches!(result, LeansigResult::Ok));
+
+            let mut pk_data = std::ptr::null_mut();
+            let mut pk_len = 0usize;
+            let result = leansig_pubkey_serialize(ctx, keypair, &mut pk_data, &mut pk_len);
+            assert!(matches!(result, LeansigResult::Ok));
+            let result =
+                leansig_verify(ctx, pk_data, pk_len, 0, msg.as_ptr(), sig_data_a, sig_len_a);
+            assert!(matches!(result, LeansigResult::Ok));
+
+            leansig_bytes_free(pk_data, pk_len);
+            leansig_bytes_free(sig_data_a, sig_len_a);
+            leansig_bytes_free(sig_data_b, sig_len_b);
+            leansig_keypair_free(keypair);
+            leansig_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn sign_deterministic_epoch_not_prepared() {
+        unsafe {
+            let ctx = new_context(SchemeId::Lifetime18Dim64Base8);
+            let keypair = new_keypair(ctx, 9);
+            // key_gen widens the requested 8 epochs to whole bottom trees, so use
+            // the prepared interval's exclusive end: the first epoch outside it.
+            let epoch = leansig_sk_prepared_end(keypair) as u32;
+            let msg = [0u8; 32];
+
+            let mut sig_data = std::ptr::null_mut();
+            let mut sig_len = 0usize;
+            let mut attempts = 0u32;
+            let result = leansig_sign_deterministic(
+                ctx,
+                keypair,
+                epoch,
+                msg.as_ptr(),
+                1,
+                &mut sig_data,
+                &mut sig_len,
+                &mut attempts,
+            );
+            assert!(matches!(result, LeansigResult::EpochNotPrepared));
+
+            leansig_keypair_free(keypair);
+            leansig_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn batch_verify_mixed_valid_invalid_across_chunk_boundaries() {
+        const N: usize = 32;
+        unsafe {
+            let ctx = new_context(SchemeId::Lifetime18Dim64Base8);
+
+            let mut pk_bufs = Vec::with_capacity(N);
+            let mut sig_bufs = Vec::with_capacity(N);
+            let mut msgs = Vec::with_capacity(N);
+            let mut expected = Vec::with_capacity(N);
+
+            for i in 0..N {
+                let keypair = new_keypair(ctx, i as u64);
+                let msg = [i as u8; 32];
+
+                let mut sig_data = std::ptr::null_mut();
+                let mut sig_len = 0usize;
+                let result =
+                    leansig_sign(ctx, keypair, 0, msg.as_ptr(), &mut sig_data, &mut sig_len);
+                assert!(matches!(result, LeansigResult::Ok));
+
+                let mut pk_data = std::ptr::null_mut();
+                let mut pk_len = 0usize;
+                let result = leansig_pubkey_serialize(ctx, keypair, &mut pk_data, &mut pk_len);
+                assert!(matches!(result, LeansigResult::Ok));
+
+                // Verify every third signature against a message other than the
+                // one signed, so invalid jobs land on both sides of whatever chunk
+                // boundaries the worker pool picks while every buffer stays
+                // well-formed (a malformed one would fail the whole call).
+                let is_valid = i % 3 != 0;
+                let mut verify_msg = msg;
+                if !is_valid {
+                    verify_msg[0] ^= 0xff;
+                }
+
+                pk_bufs.push(slice::from_raw_parts(pk_data, pk_len).to_vec());
+                sig_bufs.push(slice::from_raw_parts(sig_data, sig_len).to_vec());
+                msgs.push(verify_msg);
+                expected.push(is_valid as u8);
+
+                leansig_bytes_free(pk_data, pk_len);
+                leansig_bytes_free(sig_data, sig_len);
+                leansig_keypair_free(keypair);
+            }
+
+            let pk_ptrs: Vec<*const u8> = pk_bufs.iter().map(|b| b.as_ptr()).collect();
+            let pk_lens: Vec<usize> = pk_bufs.iter().map(|b| b.len()).collect();
+            let sig_ptrs: Vec<*const u8> = sig_bufs.iter().map(|b| b.as_ptr()).collect();
+            let sig_lens: Vec<usize> = sig_bufs.iter().map(|b| b.len()).collect();
+            let msg_ptrs: Vec<*const u8> = msgs.iter().map(|m| m.as_ptr()).collect();
+            let epochs = vec![0u32; N];
+            let mut out_results = vec![0xffu8; N];
+
+            let result = leansig_verify_batch(
+                ctx,
+                N,
+                pk_ptrs.as_ptr(),
+                pk_lens.as_ptr(),
+                epochs.as_ptr(),
+                msg_ptrs.as_ptr(),
+                sig_ptrs.as_ptr(),
+                sig_lens.as_ptr(),
+                out_results.as_mut_ptr(),
+            );
+            assert!(matches!(result, LeansigResult::Ok));
+            assert_eq!(out_results, expected);
+
+            leansig_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn batch_verify_whole_call_fails_on_malformed_buffer() {
+        unsafe {
+            let ctx = new_context(SchemeId::Lifetime18Dim64Base8);
+            let keypair = new_keypair(ctx, 10);
+            let msg = [1u8; 32];
+
+            let mut sig_data = std::ptr::null_mut();
+            let mut sig_len = 0usize;
+            let result = leansig_sign(ctx, keypair, 0, msg.as_ptr(), &mut sig_data, &mut sig_len);
+            assert!(matches!(result, LeansigResult::Ok));
+
+            let mut pk_data = std::ptr::null_mut();
+            let mut pk_len = 0usize;
+            let result = leansig_pubkey_serialize(ctx, keypair, &mut pk_data, &mut pk_len);
+            assert!(matches!(result, LeansigResult::Ok));
+
+            // One well-formed job, one job with a garbage/too-short public key:
+            // the whole call must fail before any per-item outcome is written,
+            // distinguishing this from a per-item verification failure.
+            let garbage_pk = [0u8; 3];
+            let pk_ptrs = [pk_data as *const u8, garbage_pk.as_ptr()];
+            let pk_lens = [pk_len, garbage_pk.len()];
+            let sig_ptrs = [sig_data as *const u8, sig_data as *const u8];
+            let sig_lens = [sig_len, sig_len];
+            let msgs = [msg.as_ptr(), msg.as_ptr()];
+            let epochs = [0u32, 0u32];
+            let mut out_results = [0xffu8; 2];
+
+            let result = leansig_verify_batch(
+                ctx,
+                2,
+                pk_ptrs.as_ptr(),
+                pk_lens.as_ptr(),
+                epochs.as_ptr(),
+                msgs.as_ptr(),
+                sig_ptrs.as_ptr(),
+                sig_lens.as_ptr(),
+                out_results.as_mut_ptr(),
+            );
+            assert!(matches!(result, LeansigResult::DeserializationFailed));
+            assert_eq!(out_results, [0xffu8; 2]);
+
+            leansig_bytes_free(pk_data, pk_len);
+            leansig_bytes_free(sig_data, sig_len);
+            leansig_keypair_free(keypair);
+            leansig_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn pubkey_and_seckey_size_match_serialized_length_for_every_scheme() {
+        // Guards against `leansig_{pubkey,seckey}_size`'s associated-constant
+        // lookup ever drifting from what `to_bytes()` actually produces; if it
+        // did, the allocating `leansig_{pubkey,seckey}_serialize` wrappers
+        // (which allocate exactly the "size" before calling `serialize_into`)
+        // would start spuriously returning `InvalidLength`.
+        for scheme_id in [SchemeId::Lifetime32Dim64Base8, SchemeId::Lifetime18Dim64Base8] {
+            unsafe {
+                let ctx = new_context(scheme_id);
+                let keypair = new_keypair(ctx, 3);
+
+                let mut pubkey_size = 0usize;
+                let result = leansig_pubkey_size(ctx, keypair, &mut pubkey_size);
+                assert!(matches!(result, LeansigResult::Ok));
+
+                let mut pk_data = std::ptr::null_mut();
+                let mut pk_len = 0usize;
+                let result = leansig_pubkey_serialize(ctx, keypair, &mut pk_data, &mut pk_len);
+                assert!(matches!(result, LeansigResult::Ok));
+                assert_eq!(pubkey_size, pk_len);
+                leansig_bytes_free(pk_data, pk_len);
+
+                let mut seckey_size = 0usize;
+                let result = leansig_seckey_size(ctx, keypair, &mut seckey_size);
+                assert!(matches!(result, LeansigResult::Ok));
+
+                let mut sk_data = std::ptr::null_mut();
+                let mut sk_len = 0usize;
+                let result = leansig_seckey_serialize(ctx, keypair, &mut sk_data, &mut sk_len);
+                assert!(matches!(result, LeansigResult::Ok));
+                assert_eq!(seckey_size, sk_len);
+                leansig_bytes_free(sk_data, sk_len);
+
+                leansig_keypair_free(keypair);
+                leansig_context_free(ctx);
+            }
+        }
+    }
+
+    #[test]
+    fn signature_size_matches_produced_signature_length_for_every_scheme() {
+        // Same drift guard as `pubkey_and_seckey_size_match_serialized_length_for_every_scheme`,
+        // but for `leansig_signature_size`, checked against both signing entry points.
+        for scheme_id in [SchemeId::Lifetime32Dim64Base8, SchemeId::Lifetime18Dim64Base8] {
+            unsafe {
+                let ctx = new_context(scheme_id);
+                let keypair = new_keypair(ctx, 11);
+                let msg = [2u8; 32];
+
+                let mut signature_size = 0usize;
+                let result = leansig_signature_size(ctx, &mut signature_size);
+                assert!(matches!(result, LeansigResult::Ok));
+
+                let mut sig_data = std::ptr::null_mut();
+                let mut sig_len = 0usize;
+                let result =
+                    leansig_sign(ctx, keypair, 0, msg.as_ptr(), &mut sig_data, &mut sig_len);
+                assert!(matches!(result, LeansigResult::Ok));
+                assert_eq!(signature_size, sig_len);
+                leansig_bytes_free(sig_data, sig_len);
+
+                let mut sig_data = std::ptr::null_mut();
+                let mut sig_len = 0usize;
+                let mut attempts = 0u32;
+                let result = leansig_sign_deterministic(
+                    ctx,
+                    keypair,
+                    0,
+                    msg.as_ptr(),
+                    1,
+                    &mut sig_data,
+                    &mut sig_len,
+                    &mut attempts,
+                );
+                assert!(matches!(result, LeansigResult::Ok));
+                assert_eq!(signature_size, sig_len);
+                leansig_bytes_free(sig_data, sig_len);
+
+                leansig_keypair_free(keypair);
+                leansig_context_free(ctx);
+            }
+        }
+    }
+
+    #[test]
+    fn scheme_mismatch_returns_invalid_scheme() {
+        unsafe {
+            let ctx_32 = new_context(SchemeId::Lifetime32Dim64Base8);
+            let ctx_18 = new_context(SchemeId::Lifetime18Dim64Base8);
+            let keypair_18 = new_keypair(ctx_18, 1);
+
+            let msg = [0u8; 32];
+            let mut sig_data = std::ptr::null_mut();
+            let mut sig_len = 0usize;
+            let result = leansig_sign(
+                ctx_32,
+                keypair_18,
+                0,
+                msg.as_ptr(),
+                &mut sig_data,
+                &mut sig_len,
+            );
+            assert!(matches!(result, LeansigResult::InvalidScheme));
+
+            leansig_keypair_free(keypair_18);
+            leansig_context_free(ctx_18);
+            leansig_context_free(ctx_32);
+        }
+    }
+
+    #[test]
+    fn serialize_into_too_small_buffer_returns_invalid_length_without_corrupting_out_written() {
+        unsafe {
+            let ctx = new_context(SchemeId::Lifetime18Dim64Base8);
+            let keypair = new_keypair(ctx, 2);
+
+            let mut size = 0usize;
+            let result = leansig_pubkey_size(ctx, keypair, &mut size);
+            assert!(matches!(result, LeansigResult::Ok));
+
+            let mut buf = vec![0u8; size - 1];
+            let mut out_written = SENTINEL_WRITTEN;
+            let result = leansig_pubkey_serialize_into(
+                ctx,
+                keypair,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut out_written,
+            );
+            assert!(matches!(result, LeansigResult::InvalidLength));
+            assert_eq!(out_written, SENTINEL_WRITTEN);
+
+            leansig_keypair_free(keypair);
+            leansig_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn malformed_public_key_returns_invalid_public_key_with_detail() {
+        unsafe {
+            let ctx = new_context(SchemeId::Lifetime18Dim64Base8);
+            let bad_pk = [0u8; 4];
+            let msg = [0u8; 32];
+            let sig = [0u8; 4];
+
+            let result = leansig_verify(
+                ctx,
+                bad_pk.as_ptr(),
+                bad_pk.len(),
+                0,
+                msg.as_ptr(),
+                sig.as_ptr(),
+                sig.len(),
+            );
+            assert!(matches!(result, LeansigResult::InvalidPublicKey));
+
+            let mut detail_buf = [0u8; 256];
+            let mut written = 0usize;
+            let result =
+                leansig_last_error_detail(detail_buf.as_mut_ptr(), detail_buf.len(), &mut written);
+            assert!(matches!(result, LeansigResult::Ok));
+            assert!(written > 0);
+
+            leansig_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn malformed_secret_key_returns_invalid_secret_key_with_detail() {
+        unsafe {
+            let ctx = new_context(SchemeId::Lifetime18Dim64Base8);
+            let keypair = new_keypair(ctx, 4);
+
+            let mut pk_data = std::ptr::null_mut();
+            let mut pk_len = 0usize;
+            let result = leansig_pubkey_serialize(ctx, keypair, &mut pk_data, &mut pk_len);
+            assert!(matches!(result, LeansigResult::Ok));
+
+            let bad_sk = [0u8; 4];
+            let mut restored = std::ptr::null_mut();
+            let result = leansig_keypair_restore(
+                ctx,
+                pk_data,
+                pk_len,
+                bad_sk.as_ptr(),
+                bad_sk.len(),
+                &mut restored,
+            );
+            assert!(matches!(result, LeansigResult::InvalidSecretKey));
+
+            let mut detail_buf = [0u8; 256];
+            let mut written = 0usize;
+            let result =
+                leansig_last_error_detail(detail_buf.as_mut_ptr(), detail_buf.len(), &mut written);
+            assert!(matches!(result, LeansigResult::Ok));
+            assert!(written > 0);
+
+            leansig_bytes_free(pk_data, pk_len);
+            leansig_keypair_free(keypair);
+            leansig_context_free(ctx);
+        }
+    }
+
+    #[test]
+    fn malformed_signature_returns_invalid_signature_with_detail() {
+        unsafe {
+            let ctx = new_context(SchemeId::Lifetime18Dim64Base8);
+            let keypair = new_keypair(ctx, 5);
+            let msg = [0u8; 32];
+            let bad_sig = [0u8; 4];
+
+            let result = leansig_verify_with_keypair(
+                ctx,
+                keypair,
+                0,
+                msg.as_ptr(),
+                bad_sig.as_ptr(),
+                bad_sig.len(),
+            );
+            assert!(matches!(result, LeansigResult::InvalidSignature));
+
+            let mut detail_buf = [0u8; 256];
+            let mut written = 0usize;
+            let result =
+                leansig_last_error_detail(detail_buf.as_mut_ptr(), detail_buf.len(), &mut written);
+            assert!(matches!(result, LeansigResult::Ok));
+            assert!(written > 0);
+
+            leansig_keypair_free(keypair);
+            leansig_context_free(ctx);
+        }
+    }
+}